@@ -17,6 +17,7 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use gettextrs::gettext;
 use glib::clone;
 
 use gtk::gio;
@@ -24,16 +25,24 @@ use gtk::glib;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 
+use std::cell::RefCell;
+
+use gstreamer as gst;
+
 use libadwaita::subclass::prelude::*;
 
 use crate::config::VERSION;
+use crate::extensions::ScriptRegistry;
 use crate::OrcaWindow;
 
 mod imp {
   use super::*;
 
-  #[derive(Debug, Default)]
-  pub struct OrcaApplication {}
+  #[derive(Default)]
+  pub struct OrcaApplication {
+    /// Per-application customization scripts, discovered at construction time.
+    pub scripts: RefCell<ScriptRegistry>
+  }
 
   #[glib::object_subclass]
   impl ObjectSubclass for OrcaApplication {
@@ -48,6 +57,12 @@ mod imp {
       self.parent_constructed(obj);
       obj.setup_gactions();
       obj.set_accels_for_action("app.quit", &["<primary>q"]);
+
+      // Load extensions from the user's data directory and bind the actions
+      // they declare before any window is shown.
+      let registry = ScriptRegistry::discover();
+      registry.bind_actions(obj);
+      self.scripts.replace(registry);
     }
   }
 
@@ -60,6 +75,39 @@ mod imp {
         window.upcast()
       };
       window.present();
+
+      if let Ok(window) = window.downcast::<OrcaWindow>() {
+        self.scripts.borrow().dispatch_window_activated(&window, "player");
+      }
+    }
+
+    fn open(&self, application: &Self::Type, files: &[gio::File], _hint: &str) {
+      // Nothing to play: behave exactly like a plain launch.
+      if files.is_empty() {
+        self.activate(application);
+        return;
+      }
+
+      let window = if let Some(window) = application.active_window().and_then(|window| window.downcast::<OrcaWindow>().ok()) {
+        window
+      } else {
+        OrcaWindow::new(application)
+      };
+
+      let uris = files.iter().map(|file| file.uri().to_string()).collect::<Vec<_>>();
+      window.set_queue(uris);
+      window.present();
+
+      // Let extensions tailor behavior to each file's content type.
+      let scripts = self.scripts.borrow();
+      scripts.dispatch_window_activated(&window, "player");
+      for file in files {
+        if let Ok(info) = file.query_info("standard::content-type", gio::FileQueryInfoFlags::NONE, gio::Cancellable::NONE) {
+          if let Some(mime) = info.content_type() {
+            scripts.dispatch_media_loaded(&window, file.uri().as_str(), mime.as_str());
+          }
+        }
+      }
     }
   }
 
@@ -89,20 +137,82 @@ impl OrcaApplication {
       app.show_about();
     }));
 
+    let play_action = gio::SimpleAction::new("play", None);
+    play_action.connect_activate(clone!(@weak self as app => move |_, _| {
+      if let Some(window) = app.orca_window() {
+        window.play();
+      }
+    }));
+
+    let pause_action = gio::SimpleAction::new("pause", None);
+    pause_action.connect_activate(clone!(@weak self as app => move |_, _| {
+      if let Some(window) = app.orca_window() {
+        window.pause();
+      }
+    }));
+
+    // The parameter is an absolute position in nanoseconds.
+    let seek_action = gio::SimpleAction::new("seek", Some(glib::VariantTy::new("x").unwrap()));
+    seek_action.connect_activate(clone!(@weak self as app => move |_, parameter| {
+      if let (Some(window), Some(nanos)) = (app.orca_window(), parameter.and_then(|p| p.get::<i64>())) {
+        window.seek(gst::ClockTime::from_nseconds(nanos as u64));
+      }
+    }));
+
+    let pipeline_editor_action = gio::SimpleAction::new("pipeline-editor", None);
+    pipeline_editor_action.connect_activate(clone!(@weak self as app => move |_, _| {
+      if let Some(window) = app.orca_window() {
+        window.show_pipeline_editor();
+      }
+    }));
+
+    let sleep_timer_action = gio::SimpleAction::new("sleep-timer", None);
+    sleep_timer_action.connect_activate(clone!(@weak self as app => move |_, _| {
+      if let Some(window) = app.orca_window() {
+        window.show_sleep_timer_dialog();
+      }
+    }));
+
+    // The parameter is a number of seconds to add to a running sleep timer.
+    let extend_sleep_timer_action = gio::SimpleAction::new("extend-sleep-timer", Some(glib::VariantTy::new("x").unwrap()));
+    extend_sleep_timer_action.connect_activate(clone!(@weak self as app => move |_, parameter| {
+      if let (Some(window), Some(seconds)) = (app.orca_window(), parameter.and_then(|p| p.get::<i64>())) {
+        window.extend_sleep_timer(seconds as u32);
+      }
+    }));
+
     self.add_action(&quit_action);
     self.add_action(&about_action);
+    self.add_action(&play_action);
+    self.add_action(&pause_action);
+    self.add_action(&seek_action);
+    self.add_action(&pipeline_editor_action);
+    self.add_action(&sleep_timer_action);
+    self.add_action(&extend_sleep_timer_action);
+  }
+
+  fn orca_window(&self) -> Option<OrcaWindow> {
+    self.active_window().and_then(|window| window.downcast::<OrcaWindow>().ok())
   }
 
   fn show_about(&self) {
-    // TODO: https://gitlab.gnome.org/World/Rust/libadwaita-rs/-/merge_requests/42
     let window = self.active_window().unwrap();
-    let dialog = gtk::AboutDialog::builder()
+    let about = libadwaita::AboutWindow::builder()
       .transient_for(&window)
       .modal(true)
-      .program_name("orca")
+      .application_name("orca")
+      .application_icon("com.github.wroyca.orca")
       .version(VERSION)
-      .authors(vec!["William Roy".into()])
+      .license_type(gtk::License::Gpl30)
+      .copyright("© 2022 William Roy")
+      .comments(&gettext("A media player built with GTK and libadwaita"))
+      .website("https://github.com/wroyca/orca")
+      .issue_url("https://github.com/wroyca/orca/issues")
+      .developers(vec!["William Roy".into()])
+      // Translators fill in the conventional "translator-credits" msgid with
+      // their names; gettext then returns the localized list per build.
+      .translator_credits(&gettext("translator-credits"))
       .build();
-    dialog.present();
+    about.present();
   }
 }
@@ -0,0 +1,186 @@
+// extensions.rs
+//
+// Copyright 2022 William Roy
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::rc::Rc;
+
+use glib::clone;
+
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+
+use crate::window::OrcaWindow;
+
+/// How an extension decides whether it applies to the current content, mirrored
+/// on the per-application scripts a screen reader dispatches by role.
+#[derive(Debug, Clone)]
+pub enum Matcher {
+  /// Matches media whose content type is `text/plain`, `video/mp4`, …
+  MimeType(String),
+  /// Matches a window advertising the given role (e.g. `"player"`).
+  WindowRole(String)
+}
+
+impl Matcher {
+  fn matches_mime(&self, mime: &str) -> bool {
+    matches!(self, Matcher::MimeType(pattern) if pattern == mime)
+  }
+
+  fn matches_role(&self, role: &str) -> bool {
+    matches!(self, Matcher::WindowRole(pattern) if pattern == role)
+  }
+}
+
+/// A per-application customization script. Implementors hook into the points
+/// orca exposes; every hook has a no-op default so a script overrides only
+/// what it cares about.
+pub trait Script {
+  /// The content this script is keyed to.
+  fn matcher(&self) -> Matcher;
+
+  /// `gio` actions the script contributes to the application action map.
+  fn actions(&self) -> Vec<String> {
+    Vec::new()
+  }
+
+  fn on_window_activated(&self, _window: &OrcaWindow) {}
+  fn on_media_loaded(&self, _window: &OrcaWindow, _uri: &str) {}
+  fn on_action(&self, _name: &str) {}
+}
+
+/// Holds the loaded scripts and fans orca's hooks and actions out to them.
+#[derive(Default)]
+pub struct ScriptRegistry {
+  scripts: Vec<Rc<dyn Script>>
+}
+
+impl ScriptRegistry {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Discovers extensions under `$XDG_DATA_HOME/orca/scripts`, loading each
+  /// `*.orca-script` key file into a [`DeclaredScript`]. A missing directory is
+  /// not an error — the user simply has no extensions installed.
+  pub fn discover() -> Self {
+    let mut registry = Self::new();
+
+    let mut dir = glib::user_data_dir();
+    dir.push("orca");
+    dir.push("scripts");
+
+    let directory = gio::File::for_path(&dir);
+    let enumerator = match directory.enumerate_children("standard::name", gio::FileQueryInfoFlags::NONE, gio::Cancellable::NONE) {
+      Ok(enumerator) => enumerator,
+      Err(_) => return registry
+    };
+
+    for info in enumerator.flatten() {
+      let name = info.name();
+      if name.extension().and_then(|ext| ext.to_str()) != Some("orca-script") {
+        continue;
+      }
+      let path = dir.join(&name);
+      match DeclaredScript::from_key_file(&path) {
+        Ok(script) => registry.register(Rc::new(script)),
+        Err(err) => eprintln!("orca: skipping extension {}: {}", path.display(), err)
+      }
+    }
+
+    registry
+  }
+
+  pub fn register(&mut self, script: Rc<dyn Script>) {
+    self.scripts.push(script);
+  }
+
+  /// Binds every action declared by a loaded script into `action_map`,
+  /// dispatching activations back through [`Script::on_action`].
+  pub fn bind_actions<M: IsA<gio::ActionMap>>(&self, action_map: &M) {
+    for script in &self.scripts {
+      for name in script.actions() {
+        let action = gio::SimpleAction::new(&name, None);
+        action.connect_activate(clone!(@strong script, @strong name => move |_, _| {
+          script.on_action(&name);
+        }));
+        action_map.add_action(&action);
+      }
+    }
+  }
+
+  /// Dispatches [`Script::on_window_activated`] to scripts matching `role`.
+  pub fn dispatch_window_activated(&self, window: &OrcaWindow, role: &str) {
+    for script in &self.scripts {
+      if script.matcher().matches_role(role) {
+        script.on_window_activated(window);
+      }
+    }
+  }
+
+  /// Dispatches [`Script::on_media_loaded`] to scripts matching `mime`.
+  pub fn dispatch_media_loaded(&self, window: &OrcaWindow, uri: &str, mime: &str) {
+    for script in &self.scripts {
+      if script.matcher().matches_mime(mime) {
+        script.on_media_loaded(window, uri);
+      }
+    }
+  }
+}
+
+/// A data-driven script loaded from an `*.orca-script` key file of the form:
+///
+/// ```ini
+/// [Script]
+/// Match-Mime=video/mp4
+/// Actions=my-extension-action;another-action;
+/// ```
+pub struct DeclaredScript {
+  matcher: Matcher,
+  actions: Vec<String>
+}
+
+impl DeclaredScript {
+  fn from_key_file(path: &std::path::Path) -> Result<Self, glib::Error> {
+    let key_file = glib::KeyFile::new();
+    key_file.load_from_file(path, glib::KeyFileFlags::NONE)?;
+
+    let matcher = if let Ok(mime) = key_file.string("Script", "Match-Mime") {
+      Matcher::MimeType(mime.to_string())
+    } else {
+      Matcher::WindowRole(key_file.string("Script", "Match-Role")?.to_string())
+    };
+
+    let actions = key_file
+      .string_list("Script", "Actions")
+      .map(|list| list.iter().map(|s| s.to_string()).collect())
+      .unwrap_or_default();
+
+    Ok(Self { matcher, actions })
+  }
+}
+
+impl Script for DeclaredScript {
+  fn matcher(&self) -> Matcher {
+    self.matcher.clone()
+  }
+
+  fn actions(&self) -> Vec<String> {
+    self.actions.clone()
+  }
+}
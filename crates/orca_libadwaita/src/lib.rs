@@ -19,6 +19,9 @@
 
 mod application;
 mod config;
+mod extensions;
+mod pipeline_editor;
+mod playback;
 mod window;
 
 use gettextrs::bind_textdomain_codeset;
@@ -39,7 +42,9 @@ pub fn main() {
   bind_textdomain_codeset(GETTEXT_PACKAGE, "UTF-8").expect("Unable to set the text domain encoding");
   textdomain(GETTEXT_PACKAGE).expect("Unable to switch to the text domain");
 
+  gstreamer::init().expect("Unable to initialize GStreamer");
+
   resources_register_include!("orca.gresource").expect("Could not load resources");
-  let app = OrcaApplication::new("com.github.wroyca.orca", &gio::ApplicationFlags::empty());
+  let app = OrcaApplication::new("com.github.wroyca.orca", &gio::ApplicationFlags::HANDLES_OPEN);
   std::process::exit(app.run());
 }
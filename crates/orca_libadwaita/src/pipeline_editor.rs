@@ -0,0 +1,574 @@
+// pipeline_editor.rs
+//
+// Copyright 2022 William Roy
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use glib::clone;
+
+use gtk::glib;
+use gtk::graphene;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+
+mod imp {
+  use super::*;
+
+  pub struct PipelineEditor {
+    /// The pipeline being authored; nodes on the canvas are its elements.
+    pub pipeline: gst::Pipeline,
+    /// Free-form surface the element nodes are placed onto and dragged around.
+    pub canvas: gtk::Fixed,
+    /// Transparent overlay over the canvas onto which the links are drawn.
+    pub links_area: gtk::DrawingArea,
+    /// Model backing the bus-message log, one row per message.
+    pub log: gtk::ListStore,
+    /// Container the per-property editors are rebuilt into on selection.
+    pub inspector: gtk::Box,
+    /// Established links, kept as (source, sink) pad buttons so their live
+    /// positions can be resolved each time the overlay is drawn.
+    pub links: RefCell<Vec<(gtk::Button, gtk::Button)>>,
+    /// Every pad button on the canvas, for hit-testing drag-to-link drops.
+    pub pad_buttons: RefCell<Vec<(gst::Pad, gtk::Button)>>,
+    /// The pad a link is currently being dragged from, if any.
+    pub drag_source: RefCell<Option<(gst::Pad, gtk::Button)>>,
+    /// Live cursor end of the in-flight link, in canvas coordinates.
+    pub drag_end: Cell<(f64, f64)>,
+    /// Number of nodes placed so far, used to cascade new ones on the canvas.
+    pub placed: Cell<i32>
+  }
+
+  #[glib::object_subclass]
+  impl ObjectSubclass for PipelineEditor {
+    type ParentType = gtk::Box;
+    type Type = super::PipelineEditor;
+
+    const NAME: &'static str = "OrcaPipelineEditor";
+
+    fn new() -> Self {
+      Self {
+        pipeline: gst::Pipeline::new(None),
+        canvas: gtk::Fixed::new(),
+        links_area: gtk::DrawingArea::new(),
+        log: gtk::ListStore::new(&[
+          // level, timestamp, source element, message
+          String::static_type(),
+          String::static_type(),
+          String::static_type(),
+          String::static_type()
+        ]),
+        inspector: gtk::Box::new(gtk::Orientation::Vertical, 6),
+        links: RefCell::default(),
+        pad_buttons: RefCell::default(),
+        drag_source: RefCell::default(),
+        drag_end: Cell::new((f64::NAN, f64::NAN)),
+        placed: Cell::new(0)
+      }
+    }
+  }
+
+  impl ObjectImpl for PipelineEditor {
+    fn constructed(&self, obj: &Self::Type) {
+      self.parent_constructed(obj);
+      obj.build_ui();
+      obj.watch_bus();
+    }
+  }
+
+  impl WidgetImpl for PipelineEditor {}
+  impl BoxImpl for PipelineEditor {}
+}
+
+glib::wrapper! {
+  pub struct PipelineEditor(ObjectSubclass<imp::PipelineEditor>)
+  @extends gtk::Box, gtk::Widget,
+  @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl Default for PipelineEditor {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl PipelineEditor {
+  pub fn new() -> Self {
+    glib::Object::new(&[]).expect("Failed to create PipelineEditor")
+  }
+
+  /// Lays out the editor: node canvas on the left, property inspector on the
+  /// right, bus log across the bottom.
+  fn build_ui(&self) {
+    let imp = imp::PipelineEditor::from_instance(self);
+
+    self.set_orientation(gtk::Orientation::Vertical);
+    self.append(&self.build_toolbar());
+
+    let upper = gtk::Paned::new(gtk::Orientation::Horizontal);
+    upper.set_vexpand(true);
+
+    // The links are painted on a transparent overlay above the node canvas;
+    // it passes all input through so node drags and pad links still work.
+    let overlay = gtk::Overlay::new();
+    overlay.set_child(Some(&imp.canvas));
+    imp.links_area.set_can_target(false);
+    overlay.add_overlay(&imp.links_area);
+    self.setup_links_area();
+
+    let canvas_scroll = gtk::ScrolledWindow::new();
+    canvas_scroll.set_child(Some(&overlay));
+    upper.set_start_child(Some(&canvas_scroll));
+
+    let inspector_scroll = gtk::ScrolledWindow::new();
+    inspector_scroll.set_width_request(260);
+    inspector_scroll.set_child(Some(&imp.inspector));
+    upper.set_end_child(Some(&inspector_scroll));
+
+    let lower = gtk::Paned::new(gtk::Orientation::Vertical);
+    lower.set_start_child(Some(&upper));
+    lower.set_end_child(Some(&self.build_log_view()));
+    lower.set_position(360);
+
+    self.append(&lower);
+  }
+
+  /// Builds the top toolbar: an element palette (a factory-name entry and an
+  /// add button) and transport controls that run the authored pipeline so its
+  /// bus feeds the log below.
+  fn build_toolbar(&self) -> gtk::Box {
+    let bar = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+    bar.set_margin_top(6);
+    bar.set_margin_bottom(6);
+    bar.set_margin_start(6);
+    bar.set_margin_end(6);
+
+    let entry = gtk::Entry::new();
+    entry.set_placeholder_text(Some("Element factory, e.g. videotestsrc"));
+    entry.set_hexpand(true);
+    bar.append(&entry);
+
+    let add = gtk::Button::with_label("Add");
+    add.connect_clicked(clone!(@weak self as editor, @weak entry => move |_| {
+      editor.add_element_from_entry(&entry);
+    }));
+    // Submitting the entry is equivalent to pressing Add.
+    entry.connect_activate(clone!(@weak self as editor => move |entry| {
+      editor.add_element_from_entry(entry);
+    }));
+    bar.append(&add);
+
+    let play = gtk::Button::from_icon_name("media-playback-start-symbolic");
+    play.set_tooltip_text(Some("Run the pipeline"));
+    play.connect_clicked(clone!(@weak self as editor => move |_| {
+      editor.run_pipeline();
+    }));
+    bar.append(&play);
+
+    let stop = gtk::Button::from_icon_name("media-playback-stop-symbolic");
+    stop.set_tooltip_text(Some("Stop the pipeline"));
+    stop.connect_clicked(clone!(@weak self as editor => move |_| {
+      editor.stop_pipeline();
+    }));
+    bar.append(&stop);
+
+    bar
+  }
+
+  /// Adds the factory named in `entry` to the pipeline, cascading the node
+  /// across the canvas and clearing the entry on success.
+  fn add_element_from_entry(&self, entry: &gtk::Entry) {
+    let imp = imp::PipelineEditor::from_instance(self);
+
+    let text = entry.text();
+    let name = text.trim();
+    if name.is_empty() {
+      return;
+    }
+
+    let placed = imp.placed.get();
+    let x = 20.0 + (placed % 4) as f64 * 200.0;
+    let y = 20.0 + (placed / 4) as f64 * 140.0;
+    if self.add_element(name, x, y).is_some() {
+      imp.placed.set(placed + 1);
+      entry.set_text("");
+    }
+  }
+
+  /// Runs the authored pipeline so its bus produces the state-change, error
+  /// and EOS messages shown in the log.
+  fn run_pipeline(&self) {
+    let imp = imp::PipelineEditor::from_instance(self);
+    if let Err(err) = imp.pipeline.set_state(gst::State::Playing) {
+      eprintln!("orca: could not start the authored pipeline: {err}");
+    }
+  }
+
+  /// Returns the authored pipeline to the NULL state.
+  fn stop_pipeline(&self) {
+    let imp = imp::PipelineEditor::from_instance(self);
+    let _ = imp.pipeline.set_state(gst::State::Null);
+  }
+
+  /// Builds the scrolling message log, one column per bus-message field.
+  fn build_log_view(&self) -> gtk::ScrolledWindow {
+    let imp = imp::PipelineEditor::from_instance(self);
+
+    let view = gtk::TreeView::with_model(&imp.log);
+    view.set_vexpand(true);
+
+    for (index, title) in ["Level", "Time", "Source", "Message"].iter().enumerate() {
+      let renderer = gtk::CellRendererText::new();
+      let column = gtk::TreeViewColumn::new();
+      column.set_title(title);
+      column.pack_start(&renderer, true);
+      column.add_attribute(&renderer, "text", index as i32);
+      column.set_resizable(true);
+      view.append_column(&column);
+    }
+
+    let scroll = gtk::ScrolledWindow::new();
+    scroll.set_child(Some(&view));
+    scroll
+  }
+
+  /// Adds `factory_name` to the pipeline and drops a node for it onto the
+  /// canvas at (`x`, `y`). Returns the created element, if the factory exists.
+  pub fn add_element(&self, factory_name: &str, x: f64, y: f64) -> Option<gst::Element> {
+    let imp = imp::PipelineEditor::from_instance(self);
+
+    let element = gst::ElementFactory::make(factory_name, None).ok()?;
+    imp.pipeline.add(&element).expect("Failed to add element to the pipeline");
+
+    let node = self.build_node(&element);
+    imp.canvas.put(&node, x, y);
+
+    Some(element)
+  }
+
+  /// Builds the draggable node widget for `element`: a titled frame with a row
+  /// of pad buttons the user drags links between, and that selects the element
+  /// into the inspector when its body is clicked.
+  fn build_node(&self, element: &gst::Element) -> gtk::Frame {
+    let frame = gtk::Frame::new(Some(&element.name()));
+    frame.add_css_class("card");
+
+    let body = gtk::Box::new(gtk::Orientation::Vertical, 4);
+    body.set_margin_top(6);
+    body.set_margin_bottom(6);
+    body.set_margin_start(6);
+    body.set_margin_end(6);
+
+    let pads = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+    for pad in element.pads() {
+      let button = gtk::Button::with_label(&pad.name());
+      self.setup_pad_drag(&pad, &button);
+      pads.append(&button);
+    }
+    body.append(&pads);
+    frame.set_child(Some(&body));
+
+    // Selecting the node repopulates the property inspector.
+    let select = gtk::GestureClick::new();
+    select.connect_pressed(clone!(@weak self as editor, @strong element => move |_, _, _, _| {
+      editor.inspect(&element);
+    }));
+    body.add_controller(&select);
+
+    // Dragging the node moves it around the canvas.
+    let drag = gtk::GestureDrag::new();
+    let origin = std::rc::Rc::new(RefCell::new((0.0, 0.0)));
+    drag.connect_drag_begin(clone!(@weak self as editor, @strong frame, @strong origin => move |_, _, _| {
+      let imp = imp::PipelineEditor::from_instance(&editor);
+      let (x, y) = imp.canvas.child_position(&frame);
+      origin.replace((x, y));
+    }));
+    drag.connect_drag_update(clone!(@weak self as editor, @strong frame, @strong origin => move |_, dx, dy| {
+      let imp = imp::PipelineEditor::from_instance(&editor);
+      let (x, y) = *origin.borrow();
+      imp.canvas.move_(&frame, x + dx, y + dy);
+      // Links follow the nodes they connect as the node is dragged.
+      imp.links_area.queue_draw();
+    }));
+    frame.add_controller(&drag);
+
+    frame
+  }
+
+  /// Installs the draw function that paints the established links and the
+  /// in-flight rubber-band line onto the overlay.
+  fn setup_links_area(&self) {
+    let imp = imp::PipelineEditor::from_instance(self);
+
+    imp.links_area.set_draw_func(clone!(@weak self as editor => move |_, cr, _, _| {
+      let imp = imp::PipelineEditor::from_instance(&editor);
+      cr.set_line_width(2.0);
+      cr.set_source_rgb(0.4, 0.6, 0.9);
+
+      for (src, sink) in imp.links.borrow().iter() {
+        if let (Some((ax, ay)), Some((bx, by))) = (center_of(src, &imp.links_area), center_of(sink, &imp.links_area)) {
+          cr.move_to(ax, ay);
+          cr.line_to(bx, by);
+        }
+      }
+      let _ = cr.stroke();
+
+      // The link being dragged trails from its source pad to the cursor.
+      if let Some((_, button)) = imp.drag_source.borrow().as_ref() {
+        let (ex, ey) = imp.drag_end.get();
+        if ex.is_finite() && ey.is_finite() {
+          if let Some((ax, ay)) = center_of(button, &imp.links_area) {
+            cr.move_to(ax, ay);
+            cr.line_to(ex, ey);
+            let _ = cr.stroke();
+          }
+        }
+      }
+    }));
+  }
+
+  /// Makes `button` the drag handle for `pad`: dragging from it trails a link
+  /// to the cursor and, on release over another pad, links the two (always
+  /// src → sink) and records the connector.
+  fn setup_pad_drag(&self, pad: &gst::Pad, button: &gtk::Button) {
+    let imp = imp::PipelineEditor::from_instance(self);
+    imp.pad_buttons.borrow_mut().push((pad.clone(), button.clone()));
+
+    let drag = gtk::GestureDrag::new();
+    drag.connect_drag_begin(clone!(@weak self as editor, @strong pad, @weak button => move |gesture, _, _| {
+      // Claim the sequence so the node's own drag handler does not also fire.
+      gesture.set_state(gtk::EventSequenceState::Claimed);
+      let imp = imp::PipelineEditor::from_instance(&editor);
+      imp.drag_source.replace(Some((pad.clone(), button.clone())));
+    }));
+    drag.connect_drag_update(clone!(@weak self as editor, @weak button => move |gesture, dx, dy| {
+      if let Some((sx, sy)) = gesture.start_point() {
+        let imp = imp::PipelineEditor::from_instance(&editor);
+        let local = graphene::Point::new((sx + dx) as f32, (sy + dy) as f32);
+        if let Some(point) = button.compute_point(&imp.links_area, &local) {
+          imp.drag_end.set((point.x() as f64, point.y() as f64));
+          imp.links_area.queue_draw();
+        }
+      }
+    }));
+    drag.connect_drag_end(clone!(@weak self as editor, @weak button => move |gesture, dx, dy| {
+      let imp = imp::PipelineEditor::from_instance(&editor);
+      let source = imp.drag_source.borrow_mut().take();
+      imp.drag_end.set((f64::NAN, f64::NAN));
+      imp.links_area.queue_draw();
+
+      if let (Some((src_pad, src_button)), Some((sx, sy))) = (source, gesture.start_point()) {
+        let local = graphene::Point::new((sx + dx) as f32, (sy + dy) as f32);
+        if let Some(point) = button.compute_point(&imp.links_area, &local) {
+          editor.drop_link(&src_pad, &src_button, point.x() as f64, point.y() as f64);
+        }
+      }
+    }));
+    button.add_controller(&drag);
+  }
+
+  /// Resolves the pad button dropped onto at (`x`, `y`) and, if it is a
+  /// different pad, links it to the dragged pad and draws the connector.
+  fn drop_link(&self, src_pad: &gst::Pad, src_button: &gtk::Button, x: f64, y: f64) {
+    let imp = imp::PipelineEditor::from_instance(self);
+
+    let point = graphene::Point::new(x as f32, y as f32);
+    let target = imp
+      .pad_buttons
+      .borrow()
+      .iter()
+      .find(|(_, button)| button != src_button && button.compute_bounds(&imp.links_area).map_or(false, |bounds| bounds.contains_point(&point)))
+      .map(|(pad, button)| (pad.clone(), button.clone()));
+
+    let (dst_pad, dst_button) = match target {
+      Some(target) => target,
+      None => return
+    };
+
+    // Orient the connection src → sink regardless of which end was grabbed.
+    let (src, src_button, sink, sink_button) = if src_pad.direction() == gst::PadDirection::Src {
+      (src_pad.clone(), src_button.clone(), dst_pad, dst_button)
+    } else {
+      (dst_pad, dst_button, src_pad.clone(), src_button.clone())
+    };
+
+    if let Err(err) = src.link(&sink) {
+      eprintln!("orca: could not link {} to {}: {}", src.name(), sink.name(), err);
+      return;
+    }
+    imp.links.borrow_mut().push((src_button, sink_button));
+    imp.links_area.queue_draw();
+  }
+
+  /// Rebuilds the inspector with one row per writable property of `element`,
+  /// dispatching on the `GParamSpec` type; properties of a kind that
+  /// [`Self::property_editor`] does not handle get a label but no editor.
+  fn inspect(&self, element: &gst::Element) {
+    let imp = imp::PipelineEditor::from_instance(self);
+
+    while let Some(child) = imp.inspector.first_child() {
+      imp.inspector.remove(&child);
+    }
+
+    let heading = gtk::Label::new(Some(&element.name()));
+    heading.add_css_class("title-4");
+    heading.set_xalign(0.0);
+    imp.inspector.append(&heading);
+
+    for pspec in element.list_properties() {
+      if !pspec.flags().contains(glib::ParamFlags::WRITABLE) {
+        continue;
+      }
+
+      let row = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+      let label = gtk::Label::new(Some(pspec.name()));
+      label.set_xalign(0.0);
+      label.set_hexpand(true);
+      row.append(&label);
+
+      if let Some(editor) = self.property_editor(element, &pspec) {
+        row.append(&editor);
+      }
+      imp.inspector.append(&row);
+    }
+  }
+
+  /// Builds the right editor widget for a single property, wired to write back
+  /// through `set_property` on change. Unhandled kinds yield `None`.
+  fn property_editor(&self, element: &gst::Element, pspec: &glib::ParamSpec) -> Option<gtk::Widget> {
+    let name = pspec.name().to_owned();
+
+    if let Some(spec) = pspec.downcast_ref::<glib::ParamSpecBoolean>() {
+      let switch = gtk::Switch::new();
+      switch.set_active(element.property::<bool>(&name));
+      switch.set_valign(gtk::Align::Center);
+      let _ = spec;
+      switch.connect_active_notify(clone!(@strong element, @strong name => move |switch| {
+        element.set_property(&name, switch.is_active());
+      }));
+      return Some(switch.upcast());
+    }
+
+    if let Some(spec) = pspec.downcast_ref::<glib::ParamSpecInt>() {
+      let spin = gtk::SpinButton::with_range(spec.minimum() as f64, spec.maximum() as f64, 1.0);
+      spin.set_value(element.property::<i32>(&name) as f64);
+      spin.connect_value_changed(clone!(@strong element, @strong name => move |spin| {
+        element.set_property(&name, spin.value() as i32);
+      }));
+      return Some(spin.upcast());
+    }
+
+    if let Some(spec) = pspec.downcast_ref::<glib::ParamSpecUInt>() {
+      let spin = gtk::SpinButton::with_range(spec.minimum() as f64, spec.maximum() as f64, 1.0);
+      spin.set_value(element.property::<u32>(&name) as f64);
+      spin.connect_value_changed(clone!(@strong element, @strong name => move |spin| {
+        element.set_property(&name, spin.value() as u32);
+      }));
+      return Some(spin.upcast());
+    }
+
+    if let Some(spec) = pspec.downcast_ref::<glib::ParamSpecFloat>() {
+      let spin = gtk::SpinButton::with_range(spec.minimum() as f64, spec.maximum() as f64, 0.1);
+      spin.set_digits(3);
+      spin.set_value(element.property::<f32>(&name) as f64);
+      spin.connect_value_changed(clone!(@strong element, @strong name => move |spin| {
+        element.set_property(&name, spin.value() as f32);
+      }));
+      return Some(spin.upcast());
+    }
+
+    if let Some(spec) = pspec.downcast_ref::<glib::ParamSpecDouble>() {
+      let spin = gtk::SpinButton::with_range(spec.minimum(), spec.maximum(), 0.1);
+      spin.set_digits(3);
+      spin.set_value(element.property::<f64>(&name));
+      spin.connect_value_changed(clone!(@strong element, @strong name => move |spin| {
+        element.set_property(&name, spin.value());
+      }));
+      return Some(spin.upcast());
+    }
+
+    if let Some(spec) = pspec.downcast_ref::<glib::ParamSpecEnum>() {
+      let combo = gtk::ComboBoxText::new();
+      for value in spec.enum_class().values() {
+        combo.append(Some(&value.value().to_string()), value.nick());
+      }
+      // `property_value` hands back an enum-typed `Value`; transform it to its
+      // integer discriminant to preselect the matching entry.
+      if let Some(id) = element.property_value(&name).transform::<i32>().ok().and_then(|value| value.get::<i32>().ok()) {
+        combo.set_active_id(Some(&id.to_string()));
+      }
+      let spec = spec.clone();
+      combo.connect_changed(clone!(@strong element, @strong name => move |combo| {
+        if let Some(id) = combo.active_id().and_then(|id| id.parse::<i32>().ok()) {
+          // Build the value through the enum class so the pspec receives its
+          // own enum GType rather than a plain G_TYPE_INT.
+          if let Some(value) = spec.enum_class().to_value(id) {
+            let _ = element.try_set_property_from_value(&name, &value);
+          }
+        }
+      }));
+      return Some(combo.upcast());
+    }
+
+    None
+  }
+
+  /// Watches the pipeline bus and appends each message to the log store as a
+  /// (level, timestamp, source, message) row.
+  fn watch_bus(&self) {
+    let imp = imp::PipelineEditor::from_instance(self);
+    let bus = imp.pipeline.bus().expect("pipeline has no bus");
+
+    bus
+      .add_watch_local(clone!(@weak self as editor => @default-return glib::Continue(false), move |_, message| {
+        editor.append_log(message);
+        glib::Continue(true)
+      }))
+      .expect("Failed to add the pipeline-editor bus watch");
+  }
+
+  fn append_log(&self, message: &gst::Message) {
+    let imp = imp::PipelineEditor::from_instance(self);
+
+    let level = message.type_().name();
+    let timestamp = message.timestamp().map(|t| t.to_string()).unwrap_or_else(|| "--".to_string());
+    let source = message.src().map(|src| src.name().to_string()).unwrap_or_else(|| "(pipeline)".to_string());
+    let text = match message.view() {
+      gst::MessageView::Error(err) => err.error().to_string(),
+      gst::MessageView::Warning(warn) => warn.error().to_string(),
+      gst::MessageView::Info(info) => info.error().to_string(),
+      _ => String::new()
+    };
+
+    imp.log.set(
+      &imp.log.append(),
+      &[(0, &level), (1, &timestamp), (2, &source), (3, &text)]
+    );
+  }
+}
+
+/// Centre of `widget` in the coordinate space of `relative_to`, if it can be
+/// resolved (both widgets must share a realized ancestry).
+fn center_of(widget: &impl IsA<gtk::Widget>, relative_to: &impl IsA<gtk::Widget>) -> Option<(f64, f64)> {
+  widget
+    .compute_bounds(relative_to)
+    .map(|bounds| ((bounds.x() + bounds.width() / 2.0) as f64, (bounds.y() + bounds.height() / 2.0) as f64))
+}
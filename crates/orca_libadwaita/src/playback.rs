@@ -0,0 +1,97 @@
+// playback.rs
+//
+// Copyright 2022 William Roy
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use gtk::gdk;
+use gtk::glib;
+use gtk::prelude::*;
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+
+/// Thin wrapper around a `playbin` pipeline whose decoded frames are rendered
+/// through a `gtk4paintablesink`. The sink hands out a [`gdk::Paintable`] that
+/// the window displays in a [`gtk::Picture`], so playback control lives here
+/// while the widget tree stays oblivious to GStreamer.
+pub struct Playback {
+  pipeline: gst::Element,
+  paintable: gdk::Paintable
+}
+
+impl Playback {
+  /// Builds a `playbin3` pipeline (falling back to `playbin`) wired to a
+  /// `gtk4paintablesink`. Returns an error instead of panicking when the
+  /// required GStreamer elements are missing, so a window can still open and
+  /// report that playback is unavailable.
+  pub fn new() -> Result<Self, glib::BoolError> {
+    let pipeline = gst::ElementFactory::make("playbin3", None).or_else(|_| gst::ElementFactory::make("playbin", None))?;
+
+    let sink = gst::ElementFactory::make("gtk4paintablesink", None)?;
+    let paintable = sink.property::<gdk::Paintable>("paintable");
+    pipeline.set_property("video-sink", &sink);
+
+    Ok(Self { pipeline, paintable })
+  }
+
+  /// The paintable the sink renders into, suitable for `Picture::set_paintable`.
+  pub fn paintable(&self) -> &gdk::Paintable {
+    &self.paintable
+  }
+
+  /// The pipeline bus, so callers can watch it from the main context.
+  pub fn bus(&self) -> gst::Bus {
+    self.pipeline.bus().expect("playbin has no bus")
+  }
+
+  /// Points the pipeline at `uri` without changing its state.
+  pub fn set_uri(&self, uri: &str) {
+    self.pipeline.set_property("uri", uri);
+  }
+
+  pub fn play(&self) {
+    let _ = self.pipeline.set_state(gst::State::Playing);
+  }
+
+  pub fn pause(&self) {
+    let _ = self.pipeline.set_state(gst::State::Paused);
+  }
+
+  pub fn stop(&self) {
+    let _ = self.pipeline.set_state(gst::State::Null);
+  }
+
+  /// Linear playback volume, where `1.0` is unattenuated.
+  pub fn volume(&self) -> f64 {
+    self.pipeline.property::<f64>("volume")
+  }
+
+  pub fn set_volume(&self, volume: f64) {
+    self.pipeline.set_property("volume", volume.clamp(0.0, 1.0));
+  }
+
+  /// Flushing seek to an absolute position on the nearest key frame.
+  pub fn seek(&self, position: gst::ClockTime) {
+    let _ = self.pipeline.seek_simple(gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT, position);
+  }
+}
+
+impl Drop for Playback {
+  fn drop(&mut self) {
+    let _ = self.pipeline.set_state(gst::State::Null);
+  }
+}
@@ -17,6 +17,12 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use glib::clone;
+
 use gtk::gio;
 use gtk::glib;
 use gtk::prelude::*;
@@ -26,6 +32,15 @@ use gtk::CompositeTemplate;
 use libadwaita::subclass::prelude::AdwApplicationWindowImpl;
 use libadwaita::ApplicationWindow;
 
+use gstreamer as gst;
+
+use crate::pipeline_editor::PipelineEditor;
+use crate::playback::Playback;
+
+/// Window, in seconds, over which the volume fades out before the sleep timer
+/// pauses playback.
+const SLEEP_FADE_SECONDS: u32 = 10;
+
 mod imp {
   use super::*;
 
@@ -35,7 +50,16 @@ mod imp {
     #[template_child]
     pub header_bar: TemplateChild<gtk::HeaderBar>,
     #[template_child]
-    pub label: TemplateChild<gtk::Label>
+    pub picture: TemplateChild<gtk::Picture>,
+    #[template_child]
+    pub stack: TemplateChild<gtk::Stack>,
+    pub playback: RefCell<Option<Playback>>,
+    pub queue: RefCell<VecDeque<String>>,
+    /// Seconds left on the sleep timer, the running tick source, and the
+    /// header-bar indicator button (created lazily while a timer is armed).
+    pub sleep_remaining: Cell<u32>,
+    pub sleep_source: RefCell<Option<glib::SourceId>>,
+    pub sleep_button: RefCell<Option<gtk::Button>>
   }
 
   #[glib::object_subclass]
@@ -54,7 +78,35 @@ mod imp {
     }
   }
 
-  impl ObjectImpl for OrcaWindow {}
+  impl ObjectImpl for OrcaWindow {
+    fn constructed(&self, obj: &Self::Type) {
+      self.parent_constructed(obj);
+
+      // A missing gstreamer-gtk4 / gst-plugins install must not abort window
+      // construction; log it and carry on with playback disabled.
+      let playback = match Playback::new() {
+        Ok(playback) => playback,
+        Err(err) => {
+          eprintln!("orca: media playback is unavailable, is gstreamer-gtk4 installed? ({err})");
+          return;
+        }
+      };
+      self.picture.set_paintable(Some(playback.paintable()));
+
+      // Drive the pipeline bus from the main context and forward each message
+      // to the window: end-of-stream advances the play queue and errors are
+      // logged to stderr; state changes are not reflected in the window.
+      playback
+        .bus()
+        .add_watch_local(clone!(@weak obj => @default-return glib::Continue(false), move |_, message| {
+          obj.handle_bus_message(message);
+          glib::Continue(true)
+        }))
+        .expect("Failed to add the playback bus watch");
+
+      self.playback.replace(Some(playback));
+    }
+  }
   impl WidgetImpl for OrcaWindow {}
   impl WindowImpl for OrcaWindow {}
   impl ApplicationWindowImpl for OrcaWindow {}
@@ -71,4 +123,221 @@ impl OrcaWindow {
   pub fn new<P: glib::IsA<gtk::Application>>(application: &P) -> Self {
     glib::Object::new(&[("application", application)]).expect("Failed to create OrcaWindow")
   }
+
+  /// Points the pipeline at `uri` and starts playback.
+  pub fn load_uri(&self, uri: &str) {
+    let imp = imp::OrcaWindow::from_instance(self);
+    if let Some(playback) = imp.playback.borrow().as_ref() {
+      // playbin only latches a fresh `uri` while in the NULL/READY state, so
+      // cycle the pipeline down before pointing it at the next queue entry;
+      // otherwise the second and later items silently never start.
+      playback.stop();
+      playback.set_uri(uri);
+      playback.play();
+    }
+  }
+
+  /// Replaces the play queue with `uris`, playing the first entry immediately
+  /// and advancing through the rest as each item reaches end-of-stream.
+  pub fn set_queue<I: IntoIterator<Item = String>>(&self, uris: I) {
+    let imp = imp::OrcaWindow::from_instance(self);
+    imp.queue.replace(uris.into_iter().collect());
+    self.play_next();
+  }
+
+  /// Dequeues the next URI and starts playing it; stops when the queue drains.
+  fn play_next(&self) {
+    let imp = imp::OrcaWindow::from_instance(self);
+    let next = imp.queue.borrow_mut().pop_front();
+    match next {
+      Some(uri) => self.load_uri(&uri),
+      None => {
+        if let Some(playback) = imp.playback.borrow().as_ref() {
+          playback.stop();
+        }
+      }
+    }
+  }
+
+  pub fn play(&self) {
+    let imp = imp::OrcaWindow::from_instance(self);
+    if let Some(playback) = imp.playback.borrow().as_ref() {
+      playback.play();
+    }
+  }
+
+  pub fn pause(&self) {
+    let imp = imp::OrcaWindow::from_instance(self);
+    if let Some(playback) = imp.playback.borrow().as_ref() {
+      playback.pause();
+    }
+  }
+
+  pub fn seek(&self, position: gst::ClockTime) {
+    let imp = imp::OrcaWindow::from_instance(self);
+    if let Some(playback) = imp.playback.borrow().as_ref() {
+      playback.seek(position);
+    }
+  }
+
+  /// Switches the window to the node-based pipeline editor, adding it as a
+  /// stack page the first time and packing a header button that returns to the
+  /// player view.
+  pub fn show_pipeline_editor(&self) {
+    let imp = imp::OrcaWindow::from_instance(self);
+
+    if imp.stack.child_by_name("editor").is_none() {
+      let editor = PipelineEditor::new();
+      imp.stack.add_named(&editor, Some("editor"));
+    }
+    imp.stack.set_visible_child_name("editor");
+
+    let back = gtk::Button::from_icon_name("go-previous-symbolic");
+    back.set_tooltip_text(Some("Back to player"));
+    back.connect_clicked(clone!(@weak self as window => move |button| {
+      let imp = imp::OrcaWindow::from_instance(&window);
+      imp.stack.set_visible_child_name("player");
+      imp.header_bar.remove(button);
+    }));
+    imp.header_bar.pack_start(&back);
+  }
+
+  /// Opens a small duration picker and arms the sleep timer on confirmation.
+  pub fn show_sleep_timer_dialog(&self) {
+    let dialog = gtk::Dialog::with_buttons(
+      Some("Sleep Timer"),
+      Some(self),
+      gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+      &[("Cancel", gtk::ResponseType::Cancel), ("Start", gtk::ResponseType::Accept)]
+    );
+
+    let minutes = gtk::SpinButton::with_range(1.0, 600.0, 1.0);
+    minutes.set_value(30.0);
+    minutes.set_margin_top(12);
+    minutes.set_margin_bottom(12);
+    minutes.set_margin_start(12);
+    minutes.set_margin_end(12);
+    dialog.content_area().append(&minutes);
+
+    dialog.connect_response(clone!(@weak self as window => move |dialog, response| {
+      if response == gtk::ResponseType::Accept {
+        window.start_sleep_timer(minutes.value() as u32 * 60);
+      }
+      dialog.destroy();
+    }));
+    dialog.present();
+  }
+
+  /// Arms (or re-arms) the sleep timer for `seconds`, ticking once a second and
+  /// fading the volume over the final [`SLEEP_FADE_SECONDS`] before pausing.
+  pub fn start_sleep_timer(&self, seconds: u32) {
+    let imp = imp::OrcaWindow::from_instance(self);
+    self.cancel_sleep_timer();
+    imp.sleep_remaining.set(seconds);
+
+    let button = gtk::Button::new();
+    button.add_css_class("flat");
+    button.set_tooltip_text(Some("Cancel sleep timer"));
+    button.connect_clicked(clone!(@weak self as window => move |_| {
+      window.cancel_sleep_timer();
+    }));
+    imp.header_bar.pack_end(&button);
+    imp.sleep_button.replace(Some(button));
+    self.update_sleep_indicator();
+
+    let source = glib::timeout_add_seconds_local(1, clone!(@weak self as window => @default-return glib::Continue(false), move || {
+      window.tick_sleep_timer()
+    }));
+    imp.sleep_source.replace(Some(source));
+  }
+
+  /// Adds `seconds` to a running timer; a no-op when none is armed.
+  pub fn extend_sleep_timer(&self, seconds: u32) {
+    let imp = imp::OrcaWindow::from_instance(self);
+    if imp.sleep_source.borrow().is_some() {
+      let remaining = imp.sleep_remaining.get().saturating_add(seconds);
+      imp.sleep_remaining.set(remaining);
+      // Extending back out of the fade window undoes the attenuation; the tick
+      // loop only touches the volume while inside the window, so without this
+      // an extend from within the fade would leave playback quiet for minutes.
+      if remaining > SLEEP_FADE_SECONDS {
+        if let Some(playback) = imp.playback.borrow().as_ref() {
+          playback.set_volume(1.0);
+        }
+      }
+      self.update_sleep_indicator();
+    }
+  }
+
+  /// Disarms the timer, restores full volume and removes the indicator.
+  pub fn cancel_sleep_timer(&self) {
+    let imp = imp::OrcaWindow::from_instance(self);
+    if let Some(source) = imp.sleep_source.borrow_mut().take() {
+      source.remove();
+    }
+    if let Some(button) = imp.sleep_button.borrow_mut().take() {
+      imp.header_bar.remove(&button);
+    }
+    imp.sleep_remaining.set(0);
+    if let Some(playback) = imp.playback.borrow().as_ref() {
+      playback.set_volume(1.0);
+    }
+  }
+
+  fn tick_sleep_timer(&self) -> glib::Continue {
+    let imp = imp::OrcaWindow::from_instance(self);
+    let remaining = imp.sleep_remaining.get().saturating_sub(1);
+    imp.sleep_remaining.set(remaining);
+
+    if let Some(playback) = imp.playback.borrow().as_ref() {
+      if remaining <= SLEEP_FADE_SECONDS {
+        playback.set_volume(remaining as f64 / SLEEP_FADE_SECONDS as f64);
+      }
+    }
+
+    if remaining == 0 {
+      imp.sleep_source.replace(None);
+      if let Some(playback) = imp.playback.borrow().as_ref() {
+        playback.pause();
+        // The fade to silence was only the wind-down; leave the volume where
+        // the user will find it audible again on the next play.
+        playback.set_volume(1.0);
+      }
+      if let Some(button) = imp.sleep_button.borrow_mut().take() {
+        imp.header_bar.remove(&button);
+      }
+      return glib::Continue(false);
+    }
+
+    self.update_sleep_indicator();
+    glib::Continue(true)
+  }
+
+  fn update_sleep_indicator(&self) {
+    let imp = imp::OrcaWindow::from_instance(self);
+    if let Some(button) = imp.sleep_button.borrow().as_ref() {
+      let remaining = imp.sleep_remaining.get();
+      button.set_label(&format!("\u{1F319} {}:{:02}", remaining / 60, remaining % 60));
+    }
+  }
+
+  fn handle_bus_message(&self, message: &gst::Message) {
+    use gst::MessageView;
+
+    match message.view() {
+      MessageView::Eos(..) => {
+        self.play_next();
+      }
+      MessageView::Error(err) => {
+        eprintln!(
+          "orca: playback error from {:?}: {} ({:?})",
+          err.src().map(|src| src.path_string()),
+          err.error(),
+          err.debug()
+        );
+      }
+      MessageView::StateChanged(_) => {}
+      _ => {}
+    }
+  }
 }